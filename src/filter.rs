@@ -0,0 +1,125 @@
+use crate::repology::ProjectRepo;
+
+static FILTER_KEYS: &[&str] = &["status", "repo", "category", "maintainer"];
+
+/// A single `key=value` filter applied against a [`ProjectRepo`] entry. A
+/// package is only reported when every configured filter matches.
+#[derive(Debug, Clone)]
+pub struct Filter {
+    name: String,
+    value: String,
+}
+
+impl std::str::FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        let (name, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("invalid filter {:?}, expected key=value", s))?;
+
+        if !FILTER_KEYS.contains(&name) {
+            return Err(format!(
+                "invalid filter key {:?}, expected one of {}",
+                name,
+                FILTER_KEYS.join(", ")
+            ));
+        }
+
+        Ok(Filter {
+            name: name.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+impl Filter {
+    pub fn matches(&self, project: &ProjectRepo) -> bool {
+        match self.name.as_str() {
+            "status" => project.status == self.value,
+            "repo" => project.repo == self.value,
+            "category" => project.categories.as_ref().map_or(false, |categories| {
+                categories.iter().any(|category| category == &self.value)
+            }),
+            "maintainer" => project.maintainers.as_ref().map_or(false, |maintainers| {
+                maintainers.iter().any(|m| m == &self.value)
+            }),
+            _ => unreachable!("Filter::from_str rejects unknown keys"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> ProjectRepo {
+        ProjectRepo {
+            repo: "opensuse_tumbleweed".to_string(),
+            subrepo: None,
+            srcname: None,
+            visiblename: "osutil".to_string(),
+            version: "1.2.3".to_string(),
+            maintainers: Some(vec!["danyspin97".to_string()]),
+            categories: Some(vec!["python".to_string()]),
+            status: "outdated".to_string(),
+            origversion: None,
+        }
+    }
+
+    #[test]
+    fn from_str_parses_known_keys() {
+        assert!("status=outdated".parse::<Filter>().is_ok());
+        assert!("repo=opensuse_tumbleweed".parse::<Filter>().is_ok());
+        assert!("category=python".parse::<Filter>().is_ok());
+        assert!("maintainer=danyspin97".parse::<Filter>().is_ok());
+    }
+
+    #[test]
+    fn from_str_rejects_missing_equals() {
+        assert!("status".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn from_str_rejects_unknown_key() {
+        assert!("categroy=python".parse::<Filter>().is_err());
+    }
+
+    #[test]
+    fn matches_checks_scalar_and_list_fields() {
+        let project = project();
+
+        assert!("status=outdated".parse::<Filter>().unwrap().matches(&project));
+        assert!(!"status=newest".parse::<Filter>().unwrap().matches(&project));
+
+        assert!("repo=opensuse_tumbleweed"
+            .parse::<Filter>()
+            .unwrap()
+            .matches(&project));
+
+        assert!("category=python".parse::<Filter>().unwrap().matches(&project));
+        assert!(!"category=rust".parse::<Filter>().unwrap().matches(&project));
+
+        assert!("maintainer=danyspin97"
+            .parse::<Filter>()
+            .unwrap()
+            .matches(&project));
+        assert!(!"maintainer=nobody"
+            .parse::<Filter>()
+            .unwrap()
+            .matches(&project));
+    }
+
+    #[test]
+    fn matches_is_false_when_list_field_is_absent() {
+        let mut project = project();
+        project.categories = None;
+        project.maintainers = None;
+
+        assert!(!"category=python".parse::<Filter>().unwrap().matches(&project));
+        assert!(!"maintainer=danyspin97"
+            .parse::<Filter>()
+            .unwrap()
+            .matches(&project));
+    }
+}
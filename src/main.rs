@@ -1,20 +1,29 @@
 use std::{
     env,
     fs::{self, File},
-    io::{BufRead, BufReader},
+    io::{self, BufRead, BufReader},
     path::{Path, PathBuf},
 };
 
-use clap::Parser;
-use color_eyre::eyre::{Context, ContextCompat, Result};
+use clap::{CommandFactory, Parser};
+use color_eyre::eyre::{eyre, Context, ContextCompat, Result};
 use futures::StreamExt;
 use regex::Regex;
 use reqwest::{self, Client};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_xml_rs::from_reader;
 use toml;
+use tracing::Instrument;
 use xdg::BaseDirectories;
 
+mod filter;
+mod repology;
+mod version;
+
+use filter::Filter;
+use repology::Repology;
+use version::Bump;
+
 static API: &'static str = "https://api.opensuse.org";
 
 #[macro_use]
@@ -45,6 +54,9 @@ lazy_static! {
 struct Opts {
     #[clap(subcommand)]
     subcmd: SubCommand,
+    /// Enable debug-level tracing output.
+    #[clap(short, long, global = true)]
+    verbose: bool,
 }
 
 #[derive(Parser)]
@@ -53,6 +65,45 @@ enum SubCommand {
     Outdated(Outdated),
     #[clap()]
     RequiredMacros(RequiredMacros),
+    #[clap()]
+    Completions(Completions),
+    #[clap()]
+    Man(Man),
+}
+
+#[derive(Parser)]
+struct Completions {
+    shell: Shell,
+}
+
+#[derive(Parser)]
+struct Man {}
+
+#[derive(Debug, Clone, Copy)]
+enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+    PowerShell,
+    Nushell,
+}
+
+impl std::str::FromStr for Shell {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            "powershell" => Ok(Shell::PowerShell),
+            "nushell" => Ok(Shell::Nushell),
+            _ => Err(format!(
+                "invalid shell {:?}, expected one of bash, zsh, fish, powershell, nushell",
+                s
+            )),
+        }
+    }
 }
 
 #[derive(Parser)]
@@ -61,24 +112,52 @@ struct Outdated {
     show_packages_not_found: bool,
     #[clap(long = "leap")]
     leap_ver: Option<String>,
+    #[clap(long, default_value = "plain")]
+    format: OutputFormat,
+    #[clap(long)]
+    refresh: bool,
+    #[clap(long)]
+    level: Option<Bump>,
+    #[clap(long = "filter")]
+    filters: Vec<Filter>,
 }
 
-#[derive(Parser)]
-struct RequiredMacros {}
+#[derive(Debug, Clone, Copy)]
+enum OutputFormat {
+    Plain,
+    Table,
+    Json,
+}
 
-#[derive(Deserialize)]
-struct ProjectRepo {
+impl std::str::FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "plain" => Ok(OutputFormat::Plain),
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!(
+                "invalid format {:?}, expected one of plain, table, json",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct OutdatedReport {
+    package: String,
     repo: String,
-    subrepo: Option<String>,
-    srcname: Option<String>,
-    visiblename: String,
-    version: String,
-    maintainers: Option<Vec<String>>,
-    categories: Option<Vec<String>>,
+    current_version: String,
+    newest_version: String,
     status: String,
-    origversion: Option<String>,
+    bump: Bump,
 }
 
+#[derive(Parser)]
+struct RequiredMacros {}
+
 #[derive(Deserialize)]
 struct ObsSearchPackage {
     project: String,
@@ -115,6 +194,17 @@ struct ObsSourceCollection {
 struct Config {
     username: String,
     password: String,
+    #[serde(default, rename = "leap")]
+    leap: std::collections::HashMap<String, LeapMapping>,
+    /// How long a cached Repology response stays valid, in seconds.
+    cache_ttl_secs: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct LeapMapping {
+    sle: String,
+    #[serde(default)]
+    backports: Vec<String>,
 }
 
 async fn get_maintained_pkgs() -> Result<Vec<String>> {
@@ -148,27 +238,41 @@ async fn get_maintained_pkgs() -> Result<Vec<String>> {
 }
 
 async fn handle_pkg(
-    (pkg, client, show_packages_not_found, leap_ver): (String, &Client, bool, &Option<String>),
-) -> Result<()> {
-    let repo_pkg = pkg.strip_prefix("python-").unwrap_or(&pkg);
-    let repos = client
-        .get(format!("https://repology.org/api/v1/project/{}", repo_pkg))
-        .send()
-        .await
-        .with_context(|| {
-            format!(
-                "unable to get project information from repology for package {}{}",
-                pkg,
-                if repo_pkg != pkg {
-                    format!(", searched for {}", repo_pkg)
-                } else {
-                    "".to_string()
-                }
-            )
-        })?
-        .json::<Vec<ProjectRepo>>()
-        .await
-        .with_context(|| format!("unable to deserialize json for package {}", pkg))?;
+    (pkg, client, repology, show_packages_not_found, leap_ver, level, filters): (
+        String,
+        &Client,
+        &Repology,
+        bool,
+        &Option<String>,
+        Option<Bump>,
+        &[Filter],
+    ),
+) -> Result<Option<OutdatedReport>> {
+    let span = tracing::info_span!("handle_pkg", package = %pkg);
+    handle_pkg_inner(
+        pkg,
+        client,
+        repology,
+        show_packages_not_found,
+        leap_ver,
+        level,
+        filters,
+    )
+    .instrument(span)
+    .await
+}
+
+async fn handle_pkg_inner(
+    pkg: String,
+    client: &Client,
+    repology: &Repology,
+    show_packages_not_found: bool,
+    leap_ver: &Option<String>,
+    level: Option<Bump>,
+    filters: &[Filter],
+) -> Result<Option<OutdatedReport>> {
+    tracing::debug!("querying repology");
+    let repos = repology.get_project(&pkg).await?;
     let tw_repo = repos
         .iter()
         .find(|project_repo| project_repo.repo == "opensuse_tumbleweed");
@@ -184,7 +288,7 @@ async fn handle_pkg(
             });
             if let Some(leap_repo) = leap_repo {
                 if leap_repo.version != newest_version {
-                    let text = client
+                    let response = client
                         .post(format!(
             "{}/source?cmd=branch&dryrun=1&package={}&update_project_attribute=OBS:UpdateProject",
             API, pkg
@@ -198,34 +302,43 @@ async fn handle_pkg(
                         )
                         .send()
                         .await
-                        .context("unable to get maintained projects")?
-                        .text()
-                        .await
-                        .unwrap();
+                        .context("unable to get maintained projects")?;
+                    tracing::debug!(status = %response.status(), "branch dry-run response");
+                    let text = response.text().await.unwrap();
 
                     let collection: ObsSourceCollection = from_reader(text.as_bytes())?;
-                    let data = match leap_ver.as_str() {
-                        "15.4" => ("SLE-15-SP4", vec!["SLE-15-SP3:Update", "SLE-15-SP2:Update"]),
-                        _ => unimplemented!(),
-                    };
+                    let mapping = CONFIG.leap.get(leap_ver).ok_or_else(|| {
+                        let mut known = CONFIG.leap.keys().cloned().collect::<Vec<String>>();
+                        known.sort();
+                        eyre!(
+                            "no [leap] mapping configured for Leap {}, configured versions are: {}",
+                            leap_ver,
+                            if known.is_empty() {
+                                "none".to_string()
+                            } else {
+                                known.join(", ")
+                            }
+                        )
+                    })?;
 
                     let from_latest_sle = collection
                         .packages
                         .iter()
-                        .find(|obs_package| obs_package.project == format!("SUSE:{}", data.0))
+                        .find(|obs_package| obs_package.project == format!("SUSE:{}", mapping.sle))
                         .is_some();
                     let from_latest_backports = collection
                         .packages
                         .iter()
                         .find(|obs_package| {
-                            obs_package.project == format!("openSUSE:Backports:{}", data.0)
+                            obs_package.project == format!("openSUSE:Backports:{}", mapping.sle)
                         })
                         .is_some();
                     let from_older_backports = collection
                         .packages
                         .iter()
                         .find(|obs_package| {
-                            data.1
+                            mapping
+                                .backports
                                 .iter()
                                 .find(|ver| {
                                     obs_package.project == format!("openSUSE:Backports:{}", ver)
@@ -233,14 +346,44 @@ async fn handle_pkg(
                                 .is_some()
                         })
                         .is_some();
+                    tracing::debug!(
+                        from_latest_sle,
+                        from_latest_backports,
+                        from_older_backports,
+                        "branch dry-run outcome"
+                    );
                     if from_latest_backports || (!from_latest_sle && from_older_backports) {
-                        println!("{}: {} -> {}", pkg, leap_repo.version, newest_version);
+                        let bump = version::classify(&leap_repo.version, &newest_version);
+                        if level.map_or(true, |level| bump >= level)
+                            && filters.iter().all(|f| f.matches(leap_repo))
+                        {
+                            return Ok(Some(OutdatedReport {
+                                package: pkg,
+                                repo: leap_repo.repo.to_owned(),
+                                current_version: leap_repo.version.to_owned(),
+                                newest_version,
+                                status: "outdated".to_string(),
+                                bump,
+                            }));
+                        }
                     }
                 }
             }
         } else {
             if tw_repo.status == "outdated" {
-                println!("{}: {} -> {}", pkg, tw_repo.version, newest_version);
+                let bump = version::classify(&tw_repo.version, &newest_version);
+                if level.map_or(true, |level| bump >= level)
+                    && filters.iter().all(|f| f.matches(tw_repo))
+                {
+                    return Ok(Some(OutdatedReport {
+                        package: pkg,
+                        repo: tw_repo.repo.to_owned(),
+                        current_version: tw_repo.version.to_owned(),
+                        newest_version,
+                        status: tw_repo.status.to_owned(),
+                        bump,
+                    }));
+                }
             }
         }
     } else {
@@ -249,23 +392,123 @@ async fn handle_pkg(
         }
     }
 
+    Ok(None)
+}
+
+fn render_reports(reports: &[OutdatedReport], format: OutputFormat) -> Result<()> {
+    match format {
+        OutputFormat::Plain => {
+            for report in reports {
+                println!(
+                    "{}: {} -> {}",
+                    report.package, report.current_version, report.newest_version
+                );
+            }
+        }
+        OutputFormat::Table => {
+            let widths = [
+                reports
+                    .iter()
+                    .map(|r| r.package.len())
+                    .max()
+                    .unwrap_or(0)
+                    .max("PACKAGE".len()),
+                reports
+                    .iter()
+                    .map(|r| r.current_version.len())
+                    .max()
+                    .unwrap_or(0)
+                    .max("CURRENT".len()),
+                reports
+                    .iter()
+                    .map(|r| r.newest_version.len())
+                    .max()
+                    .unwrap_or(0)
+                    .max("NEWEST".len()),
+                reports
+                    .iter()
+                    .map(|r| r.repo.len())
+                    .max()
+                    .unwrap_or(0)
+                    .max("REPO".len()),
+            ];
+            println!(
+                "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  STATUS    BUMP",
+                "PACKAGE",
+                "CURRENT",
+                "NEWEST",
+                "REPO",
+                w0 = widths[0],
+                w1 = widths[1],
+                w2 = widths[2],
+                w3 = widths[3],
+            );
+            for report in reports {
+                println!(
+                    "{:<w0$}  {:<w1$}  {:<w2$}  {:<w3$}  {:<8}  {}",
+                    report.package,
+                    report.current_version,
+                    report.newest_version,
+                    report.repo,
+                    report.status,
+                    report.bump,
+                    w0 = widths[0],
+                    w1 = widths[1],
+                    w2 = widths[2],
+                    w3 = widths[3],
+                );
+            }
+        }
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string(reports).context("unable to serialize reports to json")?
+            );
+        }
+    }
+
     Ok(())
 }
 
 async fn process_outdated(opts: Outdated) -> Result<()> {
     let client = Client::new();
-    tokio_stream::iter(get_maintained_pkgs().await?)
-        .map(|pkg| (pkg, &client, opts.show_packages_not_found, &opts.leap_ver))
+    let repology = Repology::new(
+        CONFIG.cache_ttl_secs.map(std::time::Duration::from_secs),
+        opts.refresh,
+    );
+    let format = opts.format;
+    let pkgs = get_maintained_pkgs().await?;
+    let total = pkgs.len();
+    let processed = std::sync::atomic::AtomicUsize::new(0);
+    let reports = tokio_stream::iter(pkgs)
+        .map(|pkg| {
+            (
+                pkg,
+                &client,
+                &repology,
+                opts.show_packages_not_found,
+                &opts.leap_ver,
+                opts.level,
+                opts.filters.as_slice(),
+            )
+        })
         .map(handle_pkg)
         .buffer_unordered(4)
-        .for_each(|res| async {
+        .filter_map(|res| async {
+            let done = processed.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
+            tracing::info!("{}/{} maintained packages processed", done, total);
             match res {
-                Ok(_) => {}
-                Err(err) => eprintln!("{}", err),
+                Ok(report) => report,
+                Err(err) => {
+                    eprintln!("{}", err);
+                    None
+                }
             }
         })
+        .collect::<Vec<OutdatedReport>>()
         .await;
-    Ok(())
+
+    render_reports(&reports, format)
 }
 
 fn get_pkg_name() -> Result<String> {
@@ -325,12 +568,64 @@ async fn print_required_macro(_: RequiredMacros) -> Result<()> {
     Ok(())
 }
 
+fn print_completions(args: Completions) -> Result<()> {
+    let mut cmd = Opts::command();
+    let name = cmd.get_name().to_string();
+
+    match args.shell {
+        Shell::Bash => {
+            clap_complete::generate(clap_complete::Shell::Bash, &mut cmd, name, &mut io::stdout())
+        }
+        Shell::Zsh => {
+            clap_complete::generate(clap_complete::Shell::Zsh, &mut cmd, name, &mut io::stdout())
+        }
+        Shell::Fish => {
+            clap_complete::generate(clap_complete::Shell::Fish, &mut cmd, name, &mut io::stdout())
+        }
+        Shell::PowerShell => clap_complete::generate(
+            clap_complete::Shell::PowerShell,
+            &mut cmd,
+            name,
+            &mut io::stdout(),
+        ),
+        Shell::Nushell => clap_complete_nushell::generate(
+            clap_complete_nushell::Nushell,
+            &mut cmd,
+            name,
+            &mut io::stdout(),
+        ),
+    }
+
+    Ok(())
+}
+
+fn print_man(_: Man) -> Result<()> {
+    let cmd = Opts::command();
+    clap_mangen::Man::new(cmd)
+        .render(&mut io::stdout())
+        .context("unable to render man page")
+}
+
+fn init_tracing(verbose: bool) {
+    let default_filter = if verbose { "osutil=debug" } else { "osutil=info" };
+    tracing_subscriber::fmt()
+        .with_env_filter(
+            tracing_subscriber::EnvFilter::try_from_default_env()
+                .unwrap_or_else(|_| default_filter.into()),
+        )
+        .with_target(false)
+        .init();
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     let opts: Opts = Opts::parse();
+    init_tracing(opts.verbose);
 
     match opts.subcmd {
         SubCommand::Outdated(o) => process_outdated(o).await,
         SubCommand::RequiredMacros(r) => print_required_macro(r).await,
+        SubCommand::Completions(c) => print_completions(c),
+        SubCommand::Man(m) => print_man(m),
     }
 }
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+use color_eyre::eyre::{Context, Result};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::XDG;
+
+static BASE_URL: &str = "https://repology.org/api/v1/project";
+static DEFAULT_TTL: Duration = Duration::from_secs(6 * 60 * 60);
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ProjectRepo {
+    pub repo: String,
+    pub subrepo: Option<String>,
+    pub srcname: Option<String>,
+    pub visiblename: String,
+    pub version: String,
+    pub maintainers: Option<Vec<String>>,
+    pub categories: Option<Vec<String>>,
+    pub status: String,
+    pub origversion: Option<String>,
+}
+
+/// A typed client for the Repology REST v1 API, backed by an on-disk
+/// response cache under the XDG cache directory.
+pub struct Repology {
+    client: Client,
+    ttl: Duration,
+    refresh: bool,
+}
+
+impl Repology {
+    pub fn new(ttl: Option<Duration>, refresh: bool) -> Self {
+        Self {
+            client: Client::new(),
+            ttl: ttl.unwrap_or(DEFAULT_TTL),
+            refresh,
+        }
+    }
+
+    /// Fetch the Repology project entries for `name`, stripping the
+    /// `python-` prefix packages are commonly built under, transparently
+    /// serving a cached response when it is still within the configured
+    /// TTL.
+    pub async fn get_project(&self, name: &str) -> Result<Vec<ProjectRepo>> {
+        let repo_pkg = name.strip_prefix("python-").unwrap_or(name);
+
+        if !self.refresh {
+            if let Some(cached) = self.read_cache(repo_pkg) {
+                tracing::debug!(package = %name, "serving repology response from cache");
+                return Ok(cached);
+            }
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/{}", BASE_URL, repo_pkg))
+            .send()
+            .await
+            .with_context(|| {
+                format!(
+                    "unable to get project information from repology for package {}{}",
+                    name,
+                    if repo_pkg != name {
+                        format!(", searched for {}", repo_pkg)
+                    } else {
+                        "".to_string()
+                    }
+                )
+            })?;
+        tracing::debug!(package = %name, status = %response.status(), "repology response");
+        let repos = response
+            .json::<Vec<ProjectRepo>>()
+            .await
+            .with_context(|| format!("unable to deserialize json for package {}", name))?;
+
+        self.write_cache(repo_pkg, &repos);
+
+        Ok(repos)
+    }
+
+    fn cache_path(&self, name: &str) -> Option<std::path::PathBuf> {
+        XDG.place_cache_file(format!("repology/{}.json", name)).ok()
+    }
+
+    fn read_cache(&self, name: &str) -> Option<Vec<ProjectRepo>> {
+        let path = self.cache_path(name)?;
+        let modified = std::fs::metadata(&path).ok()?.modified().ok()?;
+        if modified.elapsed().unwrap_or(Duration::MAX) > self.ttl {
+            return None;
+        }
+
+        let text = std::fs::read_to_string(&path).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    fn write_cache(&self, name: &str, repos: &[ProjectRepo]) {
+        let path = match self.cache_path(name) {
+            Some(path) => path,
+            None => return,
+        };
+        if let Ok(text) = serde_json::to_string(repos) {
+            let _ = std::fs::write(path, text);
+        }
+    }
+}
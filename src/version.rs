@@ -0,0 +1,127 @@
+use serde::Serialize;
+
+/// How large a version jump between the installed and newest version is,
+/// ignoring RPM-specific version decorations (release suffixes, `~`
+/// pre-release markers, `+git...` snapshot tags).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Bump {
+    Unknown,
+    Patch,
+    Minor,
+    Major,
+}
+
+impl std::fmt::Display for Bump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Bump::Unknown => "unknown",
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        })
+    }
+}
+
+impl std::str::FromStr for Bump {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "patch" => Ok(Bump::Patch),
+            "minor" => Ok(Bump::Minor),
+            "major" => Ok(Bump::Major),
+            _ => Err(format!(
+                "invalid level {:?}, expected one of major, minor, patch",
+                s
+            )),
+        }
+    }
+}
+
+/// Classify the upgrade from `current` to `newest` as a major, minor or
+/// patch bump, falling back to `Bump::Unknown` when either side does not
+/// parse as a semver-ish `major.minor.patch` version.
+pub fn classify(current: &str, newest: &str) -> Bump {
+    match (parse_semverish(current), parse_semverish(newest)) {
+        (Some(current), Some(newest)) => {
+            if newest.0 != current.0 {
+                if newest.0 > current.0 {
+                    Bump::Major
+                } else {
+                    Bump::Unknown
+                }
+            } else if newest.1 != current.1 {
+                if newest.1 > current.1 {
+                    Bump::Minor
+                } else {
+                    Bump::Unknown
+                }
+            } else if newest.2 > current.2 {
+                Bump::Patch
+            } else {
+                Bump::Unknown
+            }
+        }
+        _ => Bump::Unknown,
+    }
+}
+
+fn parse_semverish(version: &str) -> Option<(u64, u64, u64)> {
+    // strip RPM decorations: release suffix after '-', '+git...' snapshot
+    // tags, and '~pre-release' markers (e.g. '2.0~beta2', '1.2.3~bp154.2.3.1').
+    let version = version.split('~').next().unwrap_or(version);
+    let version = version.split('-').next().unwrap_or(version);
+    let version = version.split('+').next().unwrap_or(version);
+
+    let mut parts = version.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let minor = parts.next().unwrap_or("0").parse().ok()?;
+    let patch = parts.next().unwrap_or("0").parse().ok()?;
+
+    Some((major, minor, patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_semverish_strips_rpm_decorations() {
+        assert_eq!(parse_semverish("1.2.3"), Some((1, 2, 3)));
+        assert_eq!(parse_semverish("1.2.3-1.1"), Some((1, 2, 3)));
+        assert_eq!(parse_semverish("1.2.3+git20230101"), Some((1, 2, 3)));
+        assert_eq!(parse_semverish("2.0~beta2"), Some((2, 0, 0)));
+        assert_eq!(parse_semverish("1.2.3~bp154.2.3.1"), Some((1, 2, 3)));
+        assert_eq!(parse_semverish("1.2"), Some((1, 2, 0)));
+        assert_eq!(parse_semverish("not-a-version"), None);
+    }
+
+    #[test]
+    fn classify_detects_major_minor_patch_bumps() {
+        assert_eq!(classify("1.0.0", "2.0.0"), Bump::Major);
+        assert_eq!(classify("1.0.0", "1.1.0"), Bump::Minor);
+        assert_eq!(classify("1.0.0", "1.0.1"), Bump::Patch);
+        assert_eq!(classify("1.0.0", "1.0.0"), Bump::Unknown);
+    }
+
+    #[test]
+    fn classify_strips_rpm_decorations_before_comparing() {
+        assert_eq!(classify("2.0~beta2", "2.1"), Bump::Minor);
+        assert_eq!(classify("1.2.3~bp154.2.3.1", "1.3.0"), Bump::Minor);
+    }
+
+    #[test]
+    fn classify_does_not_misreport_downgrades_as_bumps() {
+        // same major, lower minor, higher patch: newest is actually older.
+        assert_eq!(classify("2.3.0", "2.1.5"), Bump::Unknown);
+        // lower major, higher minor: newest is actually older.
+        assert_eq!(classify("2.0.0", "1.9.5"), Bump::Unknown);
+    }
+
+    #[test]
+    fn classify_falls_back_to_unknown_on_unparseable_input() {
+        assert_eq!(classify("abc", "1.0.0"), Bump::Unknown);
+        assert_eq!(classify("1.0.0", "abc"), Bump::Unknown);
+    }
+}